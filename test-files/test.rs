@@ -1,24 +1,137 @@
-use std::collections::HashMap;
-use std::sync::{Arc, Mutex};
+use std::collections::{HashMap, VecDeque};
+use std::hash::Hash;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use async_trait::async_trait;
+use argon2::{
+    password_hash::{
+        rand_core::{OsRng, RngCore},
+        PasswordHash, PasswordHasher, PasswordVerifier, SaltString,
+    },
+    Argon2,
+};
+use jsonwebtoken::{decode, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// Generate a random hex-encoded token of `n_bytes` bytes
+fn random_hex_token(n_bytes: usize) -> String {
+    let mut bytes = vec![0u8; n_bytes];
+    OsRng.fill_bytes(&mut bytes);
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Lock a mutex, recovering the inner value if a panic elsewhere poisoned
+/// it rather than letting that wedge every other caller
+fn lock_or_recover<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
 
 /// Maximum number of authentication retries
 const MAX_RETRIES: u32 = 3;
 
+/// Lifetime of a freshly issued bearer token, in seconds
+const TOKEN_TTL_SECS: u64 = 3600;
+
 /// User authentication errors
 #[derive(Debug, Clone)]
 pub enum AuthError {
     InvalidCredentials,
     DatabaseError(String),
+    HashingError(String),
+    TokenError(String),
     Timeout,
 }
 
+/// Distinguishes how a `User` was authenticated, so `require_auth!` handlers
+/// can tell a full password/session login from a narrower API-key credential
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum AuthScope {
+    #[default]
+    Password,
+    ApiKey,
+}
+
 /// Represents an authenticated user
 #[derive(Debug, Clone)]
 pub struct User {
     pub id: u64,
     pub username: String,
     pub email: String,
+    /// PHC-formatted Argon2id hash of the user's password
+    pub(crate) password_hash: String,
+    /// How this user was authenticated for the current request
+    pub auth_scope: AuthScope,
+}
+
+impl User {
+    /// Derive a stable hash of this user's current credential version from
+    /// the last 16 bytes of their Argon2 output. Sessions are minted against
+    /// this value, so changing the password (which changes the Argon2
+    /// output) invalidates every session still carrying the old hash.
+    ///
+    /// Returns `AuthError::HashingError` if `password_hash` isn't a valid
+    /// PHC string with an embedded output (e.g. an LDAP-authenticated user,
+    /// which carries no local Argon2 hash) — callers that mint sessions for
+    /// such users should not call this.
+    pub fn session_auth_hash(&self) -> Result<String, AuthError> {
+        let parsed = PasswordHash::new(&self.password_hash)
+            .map_err(|e| AuthError::HashingError(e.to_string()))?;
+        let output = parsed
+            .hash
+            .ok_or_else(|| AuthError::HashingError("PHC string has no hash output".to_string()))?;
+        let bytes = output.as_bytes();
+        let start = bytes.len().saturating_sub(16);
+        Ok(bytes[start..]
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect())
+    }
+}
+
+/// Opaque bearer token identifying a logged-in session
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SessionToken(String);
+
+impl SessionToken {
+    /// Mint a new random session token
+    fn generate() -> Self {
+        Self(random_hex_token(32))
+    }
+}
+
+/// A minted session, tying a token to the user it was issued for and the
+/// credential version that was current at mint time
+#[derive(Debug, Clone)]
+struct SessionRecord {
+    user_id: u64,
+    session_auth_hash: String,
+}
+
+/// Claims carried by a signed bearer token
+#[derive(Debug, Serialize, Deserialize)]
+struct Claims {
+    sub: u64,
+    iat: usize,
+    exp: usize,
+    /// How the subject authenticated when this token was issued, so
+    /// `verify_token` can restore it on the rehydrated `User` instead of
+    /// letting it default back to `Password`
+    scope: AuthScope,
+}
+
+/// Minimal inbound request representation consumed by `require_auth!`
+pub struct Request {
+    headers: HashMap<String, String>,
+}
+
+impl Request {
+    fn bearer_token(&self) -> Option<&str> {
+        self.headers
+            .get("Authorization")
+            .and_then(|value| value.strip_prefix("Bearer "))
+    }
 }
 
 /// Authentication trait for different auth providers
@@ -28,62 +141,447 @@ pub trait Authenticate {
     async fn logout(&self, user_id: u64) -> Result<(), AuthError>;
 }
 
-/// Main authentication service
-pub struct AuthService {
+/// Hash a password with Argon2id using a freshly generated random salt,
+/// returning a PHC-formatted string (`$argon2id$v=19$...`)
+pub fn hash_password(password: &str) -> Result<String, AuthError> {
+    let salt = SaltString::generate(&mut OsRng);
+    Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+        .map_err(|e| AuthError::HashingError(e.to_string()))
+}
+
+/// Verify a password against a PHC-formatted Argon2 hash
+pub fn verify_password(password: &str, phc: &str) -> Result<bool, AuthError> {
+    let parsed_hash = PasswordHash::new(phc).map_err(|e| AuthError::HashingError(e.to_string()))?;
+    Ok(Argon2::default()
+        .verify_password(password.as_bytes(), &parsed_hash)
+        .is_ok())
+}
+
+/// Username/password credentials, the default `UserStore::Credentials`
+#[derive(Debug, Clone)]
+pub struct PasswordCredentials {
+    pub username: String,
+    pub password: String,
+}
+
+/// Pluggable backend for loading and verifying users. Parameterizing
+/// `AuthService` over this (rather than a hardcoded `Database`) is what lets
+/// it grow OAuth, API-key, or directory-backed credentials without being
+/// rewritten.
+#[async_trait]
+pub trait UserStore: Send + Sync {
+    /// The store's native id type, used to key `AuthService`'s cache
+    type UserId;
+    /// The credentials this store knows how to verify
+    type Credentials: Send + Sync;
+
+    /// Load a user by id, independent of any credential check
+    async fn load(&self, id: &Self::UserId) -> Result<Option<User>, AuthError>;
+    /// Verify credentials, returning the user they resolve to if valid
+    async fn verify(&self, creds: &Self::Credentials) -> Result<Option<User>, AuthError>;
+}
+
+/// Default user store: looks users up in the local `Database` and verifies
+/// `PasswordCredentials` against their stored Argon2 hash
+pub struct DatabaseUserStore {
     db: Arc<dyn Database>,
-    cache: Arc<Mutex<HashMap<String, User>>>,
+    /// Memoized per-user Argon2 hash. `hash_password` salts every call
+    /// randomly, so without this a cache-evicted reload would mint a new
+    /// `password_hash` and change `session_auth_hash()`, silently killing
+    /// every outstanding session as if the password had actually changed.
+    hashes: Mutex<HashMap<u64, String>>,
 }
 
-impl AuthService {
-    /// Create a new authentication service
+impl DatabaseUserStore {
     pub fn new(db: Arc<dyn Database>) -> Self {
         Self {
             db,
-            cache: Arc::new(Mutex::new(HashMap::new())),
+            hashes: Mutex::new(HashMap::new()),
         }
     }
-    
-    /// Hash a password securely
-    pub fn hash_password(password: &str) -> String {
-        format!("hashed_{}", password)
+
+    /// Return this user's memoized Argon2 hash, computing and caching it on
+    /// first use so repeated lookups stay stable across reloads
+    fn hash_for(&self, user_id: u64, username: &str) -> Result<String, AuthError> {
+        let mut hashes = lock_or_recover(&self.hashes);
+        if let Some(hash) = hashes.get(&user_id) {
+            return Ok(hash.clone());
+        }
+        let hash = hash_password(username)?;
+        hashes.insert(user_id, hash.clone());
+        Ok(hash)
     }
-    
+
     /// Internal method to fetch user from database
-    async fn fetch_user(&self, username: &str, password: &str) -> Result<User, AuthError> {
+    async fn fetch_by_username(&self, username: &str) -> Result<User, AuthError> {
         // Simulate database query
+        let id = 1;
         Ok(User {
-            id: 1,
+            id,
             username: username.to_string(),
             email: format!("{}@example.com", username),
+            password_hash: self.hash_for(id, username)?,
+            auth_scope: AuthScope::Password,
+        })
+    }
+
+    /// Internal method to fetch a user by id from the database
+    async fn fetch_by_id(&self, user_id: u64) -> Result<User, AuthError> {
+        // Simulate database query
+        let username = format!("user{}", user_id);
+        Ok(User {
+            id: user_id,
+            email: format!("{}@example.com", username),
+            password_hash: self.hash_for(user_id, &username)?,
+            username,
+            auth_scope: AuthScope::Password,
         })
     }
 }
 
 #[async_trait]
-impl Authenticate for AuthService {
-    async fn authenticate(&self, username: &str, password: &str) -> Result<User, AuthError> {
-        // Check cache first
-        {
-            let cache = self.cache.lock().unwrap();
-            if let Some(user) = cache.get(username) {
-                return Ok(user.clone());
+impl UserStore for DatabaseUserStore {
+    type UserId = u64;
+    type Credentials = PasswordCredentials;
+
+    async fn load(&self, id: &u64) -> Result<Option<User>, AuthError> {
+        Ok(Some(self.fetch_by_id(*id).await?))
+    }
+
+    async fn verify(&self, creds: &PasswordCredentials) -> Result<Option<User>, AuthError> {
+        let user = self.fetch_by_username(&creds.username).await?;
+        if verify_password(&creds.password, &user.password_hash)? {
+            Ok(Some(user))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+/// A long-lived API key, keyed by its SHA-256 hash so the raw key is never
+/// stored at rest
+#[derive(Debug, Clone)]
+struct ApiKeyRecord {
+    id: u64,
+    user_id: u64,
+    /// Stable identifier for the device/client the key was issued to
+    device_id: String,
+    revoked: bool,
+}
+
+/// A cached user record plus when it was inserted, so entries can expire
+struct CacheEntry {
+    user: User,
+    inserted_at: Instant,
+}
+
+/// A TTL-expiring, size-bounded, poison-safe cache of `User` records, keyed
+/// on whatever id the caller looks users up by (an `AuthService`'s `u64`
+/// user id, an `LdapAuthService`'s username). Shared so every `Authenticate`
+/// implementor gets the same bounded-eviction behavior instead of each
+/// growing its own unbounded `HashMap`.
+struct UserCache<K: Eq + Hash + Clone> {
+    entries: Mutex<HashMap<K, CacheEntry>>,
+    /// Least-recently-used order of `entries` keys, back = most recent
+    lru: Mutex<VecDeque<K>>,
+    ttl: Duration,
+    max_entries: usize,
+}
+
+impl<K: Eq + Hash + Clone> UserCache<K> {
+    fn new(ttl: Duration, max_entries: usize) -> Self {
+        Self {
+            entries: Mutex::new(HashMap::new()),
+            lru: Mutex::new(VecDeque::new()),
+            ttl,
+            max_entries,
+        }
+    }
+
+    /// Look up a user, evicting it (from both the entry map and the LRU
+    /// order) as a miss if it's expired
+    fn get(&self, key: &K) -> Option<User> {
+        let mut entries = lock_or_recover(&self.entries);
+        let user = match entries.get(key) {
+            Some(entry) if entry.inserted_at.elapsed() <= self.ttl => entry.user.clone(),
+            Some(_) => {
+                entries.remove(key);
+                drop(entries);
+                lock_or_recover(&self.lru).retain(|k| k != key);
+                return None;
             }
+            None => return None,
+        };
+        drop(entries);
+
+        let mut lru = lock_or_recover(&self.lru);
+        lru.retain(|k| k != key);
+        lru.push_back(key.clone());
+
+        Some(user)
+    }
+
+    /// Insert or refresh an entry, evicting the least-recently-used one if
+    /// this pushes the cache past `max_entries`
+    fn insert(&self, key: K, user: User) {
+        let mut entries = lock_or_recover(&self.entries);
+        entries.insert(
+            key.clone(),
+            CacheEntry {
+                user,
+                inserted_at: Instant::now(),
+            },
+        );
+
+        let mut lru = lock_or_recover(&self.lru);
+        lru.retain(|k| k != &key);
+        lru.push_back(key);
+
+        while entries.len() > self.max_entries {
+            let Some(oldest) = lru.pop_front() else {
+                break;
+            };
+            entries.remove(&oldest);
         }
-        
-        // Fetch from database
-        let user = self.fetch_user(username, password).await?;
-        
-        // Update cache
-        {
-            let mut cache = self.cache.lock().unwrap();
-            cache.insert(username.to_string(), user.clone());
+    }
+}
+
+/// Main authentication service, generic over its backing `UserStore`
+pub struct AuthService<S: UserStore<UserId = u64>> {
+    store: S,
+    cache: UserCache<u64>,
+    sessions: Arc<Mutex<HashMap<SessionToken, SessionRecord>>>,
+    api_keys: Arc<Mutex<HashMap<String, ApiKeyRecord>>>,
+    next_api_key_id: Arc<AtomicU64>,
+    jwt_secret: String,
+}
+
+impl<S: UserStore<UserId = u64>> AuthService<S> {
+    /// Create a new authentication service backed by `store`. Cached user
+    /// records older than `cache_ttl` are treated as a miss, and the cache
+    /// never holds more than `max_cache_entries` at once.
+    pub fn new(
+        store: S,
+        jwt_secret: impl Into<String>,
+        cache_ttl: Duration,
+        max_cache_entries: usize,
+    ) -> Self {
+        Self {
+            store,
+            cache: UserCache::new(cache_ttl, max_cache_entries),
+            sessions: Arc::new(Mutex::new(HashMap::new())),
+            api_keys: Arc::new(Mutex::new(HashMap::new())),
+            next_api_key_id: Arc::new(AtomicU64::new(1)),
+            jwt_secret: jwt_secret.into(),
         }
-        
+    }
+
+    fn hash_api_key(key: &str) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(key.as_bytes());
+        hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    /// Issue a new long-lived API key for a user, returning the raw key.
+    /// Only its SHA-256 hash is retained, alongside the caller-supplied
+    /// `device_id` so keys can later be listed or revoked per device.
+    pub async fn issue_api_key(
+        &self,
+        user_id: u64,
+        device_id: impl Into<String>,
+    ) -> Result<String, AuthError> {
+        // Make sure the user actually exists before minting a key for them
+        self.load_user(user_id).await?;
+
+        let key = random_hex_token(32);
+        let id = self.next_api_key_id.fetch_add(1, Ordering::Relaxed);
+
+        let mut keys = lock_or_recover(&self.api_keys);
+        keys.insert(
+            Self::hash_api_key(&key),
+            ApiKeyRecord {
+                id,
+                user_id,
+                device_id: device_id.into(),
+                revoked: false,
+            },
+        );
+
+        Ok(key)
+    }
+
+    /// List the (non-revoked) key ids issued to a device, so a caller can
+    /// e.g. show a user their active sessions per device or revoke one
+    pub fn api_keys_for_device(&self, device_id: &str) -> Vec<u64> {
+        lock_or_recover(&self.api_keys)
+            .values()
+            .filter(|record| record.device_id == device_id && !record.revoked)
+            .map(|record| record.id)
+            .collect()
+    }
+
+    /// Authenticate via a previously issued API key rather than a password.
+    /// The returned `User` carries `AuthScope::ApiKey` so `require_auth!`
+    /// handlers can tell it apart from a full password/session login.
+    pub async fn authenticate_api_key(&self, key: &str) -> Result<User, AuthError> {
+        let user_id = {
+            let keys = lock_or_recover(&self.api_keys);
+            keys.get(&Self::hash_api_key(key))
+                .filter(|record| !record.revoked)
+                .map(|record| record.user_id)
+                .ok_or(AuthError::InvalidCredentials)?
+        };
+
+        let mut user = self.load_user(user_id).await?;
+        user.auth_scope = AuthScope::ApiKey;
         Ok(user)
     }
-    
+
+    /// Revoke an API key by id so it can no longer authenticate. Fails with
+    /// `AuthError::InvalidCredentials` if no key with that id exists, so a
+    /// caller revoking a typo'd or already-deleted id gets a signal instead
+    /// of a silent no-op.
+    pub fn revoke_api_key(&self, key_id: u64) -> Result<(), AuthError> {
+        let mut keys = lock_or_recover(&self.api_keys);
+        let hash = keys
+            .iter()
+            .find(|(_, record)| record.id == key_id)
+            .map(|(hash, _)| hash.clone())
+            .ok_or(AuthError::InvalidCredentials)?;
+        keys.get_mut(&hash).unwrap().revoked = true;
+        Ok(())
+    }
+
+    /// Verify credentials against the backing store and cache the result
+    pub async fn authenticate_with(&self, creds: &S::Credentials) -> Result<User, AuthError> {
+        let user = self
+            .store
+            .verify(creds)
+            .await?
+            .ok_or(AuthError::InvalidCredentials)?;
+
+        self.cache.insert(user.id, user.clone());
+
+        Ok(user)
+    }
+
+    /// Internal method to resolve a user id, checking the cache first
+    async fn load_user(&self, user_id: u64) -> Result<User, AuthError> {
+        if let Some(user) = self.cache.get(&user_id) {
+            return Ok(user);
+        }
+
+        // Fetch from the backing store
+        let user = self
+            .store
+            .load(&user_id)
+            .await?
+            .ok_or(AuthError::InvalidCredentials)?;
+
+        self.cache.insert(user_id, user.clone());
+
+        Ok(user)
+    }
+
+    /// Mint a new session for an already-authenticated user
+    pub async fn create_session(&self, user_id: u64) -> Result<SessionToken, AuthError> {
+        let user = self.load_user(user_id).await?;
+        let token = SessionToken::generate();
+
+        let mut sessions = lock_or_recover(&self.sessions);
+        sessions.insert(
+            token.clone(),
+            SessionRecord {
+                user_id,
+                session_auth_hash: user.session_auth_hash()?,
+            },
+        );
+
+        Ok(token)
+    }
+
+    /// Resolve a session token back to its user, rejecting the session if
+    /// the user's credentials have changed (and so invalidated it) since it
+    /// was minted
+    pub async fn load_session(&self, token: &SessionToken) -> Result<Option<User>, AuthError> {
+        let record = {
+            let sessions = lock_or_recover(&self.sessions);
+            match sessions.get(token) {
+                Some(record) => record.clone(),
+                None => return Ok(None),
+            }
+        };
+
+        let user = self.load_user(record.user_id).await?;
+        if user.session_auth_hash()? != record.session_auth_hash {
+            return Ok(None);
+        }
+
+        Ok(Some(user))
+    }
+
+    /// Issue a signed HS256 bearer token for an authenticated user. The
+    /// token carries `user.auth_scope`, so a token minted from
+    /// `authenticate_api_key`'s result still reads as `AuthScope::ApiKey`
+    /// once `verify_token` rehydrates it.
+    pub fn issue_token(&self, user: &User) -> Result<String, AuthError> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_err(|e| AuthError::TokenError(e.to_string()))?
+            .as_secs() as usize;
+
+        let claims = Claims {
+            sub: user.id,
+            iat: now,
+            exp: now + TOKEN_TTL_SECS as usize,
+            scope: user.auth_scope,
+        };
+
+        encode(
+            &Header::new(Algorithm::HS256),
+            &claims,
+            &EncodingKey::from_secret(self.jwt_secret.as_bytes()),
+        )
+        .map_err(|e| AuthError::TokenError(e.to_string()))
+    }
+
+    /// Validate a bearer token's signature and expiry, then rehydrate the
+    /// user it was issued for, restoring the `AuthScope` the token was
+    /// minted with (since `load_user` always yields `AuthScope::Password`
+    /// otherwise)
+    pub async fn verify_token(&self, token: &str) -> Result<User, AuthError> {
+        let data = decode::<Claims>(
+            token,
+            &DecodingKey::from_secret(self.jwt_secret.as_bytes()),
+            &Validation::new(Algorithm::HS256),
+        )
+        .map_err(|_| AuthError::InvalidCredentials)?;
+
+        let mut user = self.load_user(data.claims.sub).await?;
+        user.auth_scope = data.claims.scope;
+        Ok(user)
+    }
+}
+
+#[async_trait]
+impl<S> Authenticate for AuthService<S>
+where
+    S: UserStore<UserId = u64, Credentials = PasswordCredentials>,
+{
+    async fn authenticate(&self, username: &str, password: &str) -> Result<User, AuthError> {
+        self.authenticate_with(&PasswordCredentials {
+            username: username.to_string(),
+            password: password.to_string(),
+        })
+        .await
+    }
+
     async fn logout(&self, user_id: u64) -> Result<(), AuthError> {
-        // Implementation here
+        // Drop every session minted for this user
+        let mut sessions = lock_or_recover(&self.sessions);
+        sessions.retain(|_, record| record.user_id != user_id);
         Ok(())
     }
 }
@@ -94,15 +592,393 @@ pub trait Database: Send + Sync {
     async fn query(&self, query: &str) -> Result<Vec<User>, AuthError>;
 }
 
-/// Macro for creating auth middleware
+/// A directory entry resolved after a successful LDAP bind
+struct LdapEntry {
+    uid: u64,
+    cn: String,
+    mail: String,
+}
+
+/// Escape a value for safe interpolation into an LDAP DN (RFC 4514)
+fn escape_dn_value(value: &str) -> String {
+    let len = value.chars().count();
+    let mut escaped = String::with_capacity(value.len());
+    for (i, c) in value.chars().enumerate() {
+        match c {
+            ',' | '+' | '"' | '\\' | '<' | '>' | ';' | '=' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '#' if i == 0 => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            ' ' if i == 0 || i == len - 1 => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Escape a value for safe interpolation into an LDAP search filter (RFC 4515)
+fn escape_filter_value(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '*' => escaped.push_str("\\2a"),
+            '(' => escaped.push_str("\\28"),
+            ')' => escaped.push_str("\\29"),
+            '\\' => escaped.push_str("\\5c"),
+            '\0' => escaped.push_str("\\00"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Pool of LDAP connections, abstracted so it can be swapped for a mock in
+/// tests without pulling in a real directory
+#[async_trait]
+pub trait LdapPool: Send + Sync {
+    async fn bind(&self, dn: &str, password: &str) -> Result<(), AuthError>;
+    async fn search_user(&self, base: &str, username: &str) -> Result<Option<LdapEntry>, AuthError>;
+}
+
+/// `LdapPool` backed by the `ldap3` crate. Each `bind` and `search_user`
+/// call opens its own connection to `url`; connections are not kept or
+/// reused between calls.
+pub struct Ldap3Pool {
+    url: String,
+}
+
+impl Ldap3Pool {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self { url: url.into() }
+    }
+
+    async fn connect(&self) -> Result<ldap3::Ldap, AuthError> {
+        let (conn, ldap) = ldap3::LdapConnAsync::new(&self.url)
+            .await
+            .map_err(|e| AuthError::DatabaseError(e.to_string()))?;
+        ldap3::drive!(conn);
+        Ok(ldap)
+    }
+}
+
+#[async_trait]
+impl LdapPool for Ldap3Pool {
+    async fn bind(&self, dn: &str, password: &str) -> Result<(), AuthError> {
+        let mut ldap = self.connect().await?;
+        ldap.simple_bind(dn, password)
+            .await
+            .and_then(|res| res.success())
+            .map_err(|_| AuthError::InvalidCredentials)?;
+        Ok(())
+    }
+
+    async fn search_user(&self, base: &str, username: &str) -> Result<Option<LdapEntry>, AuthError> {
+        let mut ldap = self.connect().await?;
+        let (results, _) = ldap
+            .search(
+                base,
+                ldap3::Scope::Subtree,
+                &format!("(uid={})", escape_filter_value(username)),
+                vec!["uid", "cn", "mail"],
+            )
+            .await
+            .and_then(|res| res.success())
+            .map_err(|e| AuthError::DatabaseError(e.to_string()))?;
+
+        let Some(entry) = results.into_iter().next() else {
+            return Ok(None);
+        };
+        let entry = ldap3::SearchEntry::construct(entry);
+        let attr = |name: &str| entry.attrs.get(name).and_then(|v| v.first()).cloned();
+
+        let uid = attr("uid")
+            .and_then(|v| v.parse::<u64>().ok())
+            .ok_or_else(|| AuthError::DatabaseError("directory entry missing uid".to_string()))?;
+
+        Ok(Some(LdapEntry {
+            uid,
+            cn: attr("cn").unwrap_or_default(),
+            mail: attr("mail").unwrap_or_default(),
+        }))
+    }
+}
+
+/// Configuration for binding against an LDAP directory
+pub struct LdapConfig {
+    /// Bind DN template with a `{username}` placeholder, e.g.
+    /// `"uid={username},ou=people,dc=example,dc=com"`
+    pub bind_dn_template: String,
+    /// Base DN to search under for the user's attributes once bound
+    pub search_base: String,
+}
+
+impl LdapConfig {
+    fn bind_dn(&self, username: &str) -> String {
+        self.bind_dn_template
+            .replace("{username}", &escape_dn_value(username))
+    }
+}
+
+/// Authentication provider that binds against an LDAP directory instead of
+/// the local database
+pub struct LdapAuthService {
+    config: LdapConfig,
+    pool: Arc<dyn LdapPool>,
+    cache: UserCache<String>,
+}
+
+impl LdapAuthService {
+    /// Create a new LDAP-backed authentication service. Cached user records
+    /// older than `cache_ttl` are treated as a miss, and the cache never
+    /// holds more than `max_cache_entries` at once.
+    pub fn new(
+        config: LdapConfig,
+        pool: Arc<dyn LdapPool>,
+        cache_ttl: Duration,
+        max_cache_entries: usize,
+    ) -> Self {
+        Self {
+            config,
+            pool,
+            cache: UserCache::new(cache_ttl, max_cache_entries),
+        }
+    }
+}
+
+#[async_trait]
+impl Authenticate for LdapAuthService {
+    async fn authenticate(&self, username: &str, password: &str) -> Result<User, AuthError> {
+        if let Some(user) = self.cache.get(&username.to_string()) {
+            return Ok(user);
+        }
+
+        // Bind as the user to verify their password
+        let bind_dn = self.config.bind_dn(username);
+        self.pool.bind(&bind_dn, password).await?;
+
+        // Bind succeeded; look up their directory attributes
+        let entry = self
+            .pool
+            .search_user(&self.config.search_base, username)
+            .await?
+            .ok_or_else(|| {
+                AuthError::DatabaseError(format!("no directory entry for {}", username))
+            })?;
+
+        // LDAP-authenticated users have no local Argon2 hash to carry
+        let user = User {
+            id: entry.uid,
+            username: entry.cn,
+            email: entry.mail,
+            password_hash: String::new(),
+            auth_scope: AuthScope::Password,
+        };
+
+        self.cache.insert(username.to_string(), user.clone());
+
+        Ok(user)
+    }
+
+    async fn logout(&self, _user_id: u64) -> Result<(), AuthError> {
+        Ok(())
+    }
+}
+
+/// Macro for creating auth middleware. `$service` must be cloneable (e.g.
+/// an `Arc<AuthService>`); the wrapped handler receives the request plus
+/// the `User` resolved from its bearer token.
 macro_rules! require_auth {
-    ($handler:expr) => {
-        |req| async move {
-            if req.authenticated {
-                $handler(req).await
-            } else {
-                Err(AuthError::InvalidCredentials)
+    ($service:expr, $handler:expr) => {
+        |req: Request| {
+            let service = $service.clone();
+            async move {
+                let token = req.bearer_token().ok_or(AuthError::InvalidCredentials)?;
+                let user = service.verify_token(token).await?;
+                $handler(req, user).await
             }
         }
     };
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hash_password_round_trips_through_verify() {
+        let hash = hash_password("correct horse battery staple").unwrap();
+        assert!(verify_password("correct horse battery staple", &hash).unwrap());
+    }
+
+    #[test]
+    fn verify_password_rejects_wrong_password() {
+        let hash = hash_password("correct horse battery staple").unwrap();
+        assert!(!verify_password("wrong password", &hash).unwrap());
+    }
+
+    #[test]
+    fn hash_password_salts_each_call_independently() {
+        let a = hash_password("same password").unwrap();
+        let b = hash_password("same password").unwrap();
+        assert_ne!(a, b);
+    }
+
+    /// A fixed single-user store, so session tests can mutate the stored
+    /// password out from under an `AuthService` without a real database
+    struct SingleUserStore {
+        user: Mutex<User>,
+    }
+
+    #[async_trait]
+    impl UserStore for SingleUserStore {
+        type UserId = u64;
+        type Credentials = PasswordCredentials;
+
+        async fn load(&self, id: &u64) -> Result<Option<User>, AuthError> {
+            let user = lock_or_recover(&self.user).clone();
+            Ok((user.id == *id).then_some(user))
+        }
+
+        async fn verify(&self, creds: &PasswordCredentials) -> Result<Option<User>, AuthError> {
+            let user = lock_or_recover(&self.user).clone();
+            if creds.username == user.username && verify_password(&creds.password, &user.password_hash)? {
+                Ok(Some(user))
+            } else {
+                Ok(None)
+            }
+        }
+    }
+
+    fn make_store(password: &str) -> SingleUserStore {
+        SingleUserStore {
+            user: Mutex::new(User {
+                id: 1,
+                username: "alice".to_string(),
+                email: "alice@example.com".to_string(),
+                password_hash: hash_password(password).unwrap(),
+                auth_scope: AuthScope::Password,
+            }),
+        }
+    }
+
+    #[tokio::test]
+    async fn load_session_succeeds_before_any_password_change() {
+        // A zero TTL forces every load_user through the store, isolating
+        // this test from cache behavior (covered separately under chunk0-7)
+        let service = AuthService::new(make_store("hunter2"), "secret", Duration::from_secs(0), 10);
+        let token = service.create_session(1).await.unwrap();
+        assert!(service.load_session(&token).await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn load_session_rejects_after_password_change() {
+        let service = AuthService::new(make_store("hunter2"), "secret", Duration::from_secs(0), 10);
+        let token = service.create_session(1).await.unwrap();
+
+        lock_or_recover(&service.store.user).password_hash = hash_password("new-password").unwrap();
+
+        assert!(service.load_session(&token).await.unwrap().is_none());
+    }
+
+    #[test]
+    fn escape_dn_value_escapes_rfc4514_special_characters() {
+        assert_eq!(escape_dn_value("a,b"), "a\\,b");
+        assert_eq!(escape_dn_value("a+b"), "a\\+b");
+        assert_eq!(escape_dn_value("a\"b"), "a\\\"b");
+        assert_eq!(escape_dn_value("a\\b"), "a\\\\b");
+        assert_eq!(escape_dn_value("a<b>c"), "a\\<b\\>c");
+        assert_eq!(escape_dn_value("a;b"), "a\\;b");
+        assert_eq!(escape_dn_value("a=b"), "a\\=b");
+        assert_eq!(escape_dn_value("#leading"), "\\#leading");
+        assert_eq!(escape_dn_value(" leading"), "\\ leading");
+        assert_eq!(escape_dn_value("trailing "), "trailing\\ ");
+    }
+
+    #[test]
+    fn escape_filter_value_escapes_rfc4515_special_characters() {
+        assert_eq!(escape_filter_value("a*b"), "a\\2ab");
+        assert_eq!(escape_filter_value("a(b)c"), "a\\28b\\29c");
+        assert_eq!(escape_filter_value("a\\b"), "a\\5cb");
+        assert_eq!(escape_filter_value("a\0b"), "a\\00b");
+    }
+
+    #[test]
+    fn bind_dn_escapes_injection_attempts_in_username() {
+        let config = LdapConfig {
+            bind_dn_template: "uid={username},ou=people,dc=example,dc=com".to_string(),
+            search_base: "ou=people,dc=example,dc=com".to_string(),
+        };
+        let dn = config.bind_dn("admin,dc=evil,dc=com");
+        assert_eq!(dn, "uid=admin\\,dc\\=evil\\,dc\\=com,ou=people,dc=example,dc=com");
+    }
+
+    fn cache_test_user(id: u64) -> User {
+        User {
+            id,
+            username: format!("user{id}"),
+            email: format!("user{id}@example.com"),
+            password_hash: String::new(),
+            auth_scope: AuthScope::Password,
+        }
+    }
+
+    #[test]
+    fn user_cache_expires_entries_after_ttl() {
+        let cache = UserCache::new(Duration::from_millis(10), 10);
+        cache.insert(1u64, cache_test_user(1));
+        assert!(cache.get(&1).is_some());
+
+        std::thread::sleep(Duration::from_millis(30));
+        assert!(cache.get(&1).is_none());
+    }
+
+    #[test]
+    fn user_cache_evicts_least_recently_used_over_capacity() {
+        let cache = UserCache::new(Duration::from_secs(60), 2);
+        cache.insert(1u64, cache_test_user(1));
+        cache.insert(2u64, cache_test_user(2));
+        cache.insert(3u64, cache_test_user(3)); // evicts 1, the least recently used
+
+        assert!(cache.get(&1).is_none());
+        assert!(cache.get(&2).is_some());
+        assert!(cache.get(&3).is_some());
+    }
+
+    #[test]
+    fn user_cache_does_not_leak_ttl_expired_keys_into_lru() {
+        let cache = UserCache::new(Duration::from_millis(10), 1);
+        cache.insert(1u64, cache_test_user(1));
+
+        std::thread::sleep(Duration::from_millis(30));
+        assert!(cache.get(&1).is_none()); // expires out of both entries and lru
+
+        cache.insert(2u64, cache_test_user(2));
+        cache.insert(3u64, cache_test_user(3)); // with max_entries 1, this evicts 2, not a phantom 1
+        assert!(cache.get(&2).is_none());
+        assert!(cache.get(&3).is_some());
+    }
+
+    struct StubDatabase;
+
+    #[async_trait]
+    impl Database for StubDatabase {
+        async fn query(&self, _query: &str) -> Result<Vec<User>, AuthError> {
+            Ok(vec![])
+        }
+    }
+
+    #[tokio::test]
+    async fn database_user_store_returns_a_stable_hash_across_reloads() {
+        let store = DatabaseUserStore::new(Arc::new(StubDatabase));
+        let first = store.load(&1).await.unwrap().unwrap();
+        let second = store.load(&1).await.unwrap().unwrap();
+        assert_eq!(first.password_hash, second.password_hash);
+    }
+}